@@ -34,6 +34,16 @@ const TILDE: &str = "~";
 /// If the path starts with `~`, it is replaced with the given `home_dir`.
 /// Otherwise, the original path is returned unchanged.
 ///
+/// `~` only matches as a standalone first component: it must either be the
+/// entire path or be immediately followed by a path separator. A leading
+/// segment like `~foo` is left untouched (see [`expand_tilde_user_with`]
+/// for `~name` expansion), and a `~` that is not the first component (e.g.
+/// `foo/~`) is never expanded.
+///
+/// If `home_dir` is the filesystem root (as can happen on minimal or
+/// containerized setups), expansion still produces a clean path: `~`
+/// becomes `/` and `~/some/dir` becomes `/some/dir`, never `//some/dir`.
+///
 /// # Example
 ///
 /// ```rust
@@ -116,6 +126,386 @@ pub fn home_dir() -> Result<PathBuf, HomeDirError> {
     Ok(home_dir)
 }
 
+/// Rewrites a leading `home_dir` prefix in `path` back to `~`.
+///
+/// If `path` starts with `home_dir`, the prefix is replaced with `~`.
+/// Otherwise, the original path is returned unchanged. This is the inverse
+/// of [`expand_tilde_with`].
+///
+/// # Example
+///
+/// ```rust
+/// use zeroten_expand_tilde::fold_tilde_with;
+/// use std::path::{Path, PathBuf};
+///
+/// let home = "/home/user";
+/// let path = Path::new("/home/user/docs");
+/// assert_eq!(fold_tilde_with(path, home), PathBuf::from("~/docs"));
+/// ```
+pub fn fold_tilde_with<P, H>(path: &P, home_dir: H) -> Cow<'_, Path>
+where
+    P: AsRef<Path> + ?Sized,
+    H: AsRef<Path>,
+{
+    fn inner<'a>(path: &'a Path, home_dir: &Path) -> Cow<'a, Path> {
+        path.strip_prefix(home_dir).map_or_else(
+            |_| path.into(),
+            |stripped| Path::new(TILDE).join(stripped).into(),
+        )
+    }
+
+    inner(path.as_ref(), home_dir.as_ref())
+}
+
+/// Rewrites a leading current-user home-directory prefix in `path` back to
+/// `~`.
+///
+/// This is the inverse of [`expand_tilde`].
+///
+/// # Errors
+///
+/// - [`HomeDirError::NotFounded`] if the home directory cannot be determined
+/// - [`HomeDirError::Empty`] if the home directory is empty
+pub fn fold_tilde<P>(path: &P) -> Result<Cow<'_, Path>, HomeDirError>
+where
+    P: AsRef<Path> + ?Sized,
+{
+    let home_dir = home_dir()?;
+    Ok(fold_tilde_with(path, home_dir))
+}
+
+/// Substitutes `$VAR`/`${VAR}` references in `input` using `lookup`.
+///
+/// Operates on the path's string form; the caller re-parses the result as a
+/// [`Path`]. A lone `$` not followed by a valid variable name (or an
+/// unterminated `${`) is left as-is.
+///
+/// # Errors
+///
+/// Returns [`HomeDirError::VarNotFound`] if `lookup` returns `None` for a
+/// referenced variable.
+#[cfg(feature = "expand-vars")]
+fn substitute_vars(
+    input: &str,
+    lookup: &dyn Fn(&str) -> Option<String>,
+) -> Result<String, HomeDirError> {
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(dollar) = rest.find('$') {
+        output.push_str(&rest[..dollar]);
+        rest = &rest[dollar + 1..];
+
+        let (name, remainder) = if let Some(braced) = rest.strip_prefix('{') {
+            if let Some(end) = braced.find('}') {
+                (&braced[..end], &braced[end + 1..])
+            } else {
+                output.push_str("${");
+                rest = braced;
+                continue;
+            }
+        } else {
+            let end = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            (&rest[..end], &rest[end..])
+        };
+
+        if name.is_empty() {
+            output.push('$');
+            output.push_str(&rest[..rest.len() - remainder.len()]);
+        } else {
+            let value = lookup(name).ok_or_else(|| HomeDirError::VarNotFound {
+                name: name.to_owned(),
+            })?;
+            output.push_str(&value);
+        }
+
+        rest = remainder;
+    }
+
+    output.push_str(rest);
+    Ok(output)
+}
+
+/// Expands `$VAR`/`${VAR}` references in a path using `lookup`.
+///
+/// If `path` contains no `$`, it is returned unchanged and borrowed.
+///
+/// # Example
+///
+/// ```rust
+/// use zeroten_expand_tilde::expand_vars_with;
+/// use std::path::{Path, PathBuf};
+///
+/// let path = Path::new("$HOME/docs");
+/// assert_eq!(
+///     expand_vars_with(path, |name| (name == "HOME").then(|| "/home/user".to_owned())).unwrap(),
+///     PathBuf::from("/home/user/docs")
+/// );
+/// ```
+///
+/// # Errors
+///
+/// Returns [`HomeDirError::VarNotFound`] if `lookup` returns `None` for a
+/// referenced variable.
+#[cfg(feature = "expand-vars")]
+pub fn expand_vars_with<P, F>(path: &P, lookup: F) -> Result<Cow<'_, Path>, HomeDirError>
+where
+    P: AsRef<Path> + ?Sized,
+    F: Fn(&str) -> Option<String>,
+{
+    fn inner<'a>(
+        path: &'a Path,
+        lookup: &dyn Fn(&str) -> Option<String>,
+    ) -> Result<Cow<'a, Path>, HomeDirError> {
+        let Some(s) = path.to_str() else {
+            return Ok(path.into());
+        };
+
+        if !s.contains('$') {
+            return Ok(path.into());
+        }
+
+        Ok(PathBuf::from(substitute_vars(s, lookup)?).into())
+    }
+
+    inner(path.as_ref(), &lookup)
+}
+
+/// Expands `$VAR`/`${VAR}` references in a path using [`std::env::var`].
+///
+/// # Errors
+///
+/// Returns [`HomeDirError::VarNotFound`] if a referenced variable is not set.
+#[cfg(feature = "expand-vars")]
+pub fn expand_vars<P>(path: &P) -> Result<Cow<'_, Path>, HomeDirError>
+where
+    P: AsRef<Path> + ?Sized,
+{
+    expand_vars_with(path, |name| std::env::var(name).ok())
+}
+
+/// Expands a leading `~` and any `$VAR`/`${VAR}` references in a path, using
+/// `home_dir` for the tilde and `lookup` for variables.
+///
+/// Tilde expansion runs first (see [`expand_tilde_with`]), then variable
+/// substitution runs over the result's string form, which is re-parsed as a
+/// [`Path`]. If neither a `~` nor a `$` is present, the path is returned
+/// unchanged and borrowed.
+///
+/// # Example
+///
+/// ```rust
+/// use zeroten_expand_tilde::expand_full_with;
+/// use std::path::{Path, PathBuf};
+///
+/// let path = Path::new("~/$PROJECT");
+/// assert_eq!(
+///     expand_full_with(path, "/home/user", |name| {
+///         (name == "PROJECT").then(|| "crate".to_owned())
+///     })
+///     .unwrap(),
+///     PathBuf::from("/home/user/crate")
+/// );
+/// ```
+///
+/// # Errors
+///
+/// Returns [`HomeDirError::VarNotFound`] if `lookup` returns `None` for a
+/// referenced variable.
+#[cfg(feature = "expand-vars")]
+pub fn expand_full_with<P, H, F>(
+    path: &P,
+    home_dir: H,
+    lookup: F,
+) -> Result<Cow<'_, Path>, HomeDirError>
+where
+    P: AsRef<Path> + ?Sized,
+    H: AsRef<Path>,
+    F: Fn(&str) -> Option<String>,
+{
+    match expand_tilde_with(path, home_dir) {
+        Cow::Borrowed(path) => expand_vars_with(path, lookup),
+        Cow::Owned(path) => Ok(expand_vars_with(&path, lookup)?.into_owned().into()),
+    }
+}
+
+/// Expands a leading `~` and any `$VAR`/`${VAR}` references in a path, using
+/// the current user's home directory and [`std::env::var`].
+///
+/// # Errors
+///
+/// - [`HomeDirError::NotFounded`] if the home directory cannot be determined
+/// - [`HomeDirError::Empty`] if the home directory is empty
+/// - [`HomeDirError::VarNotFound`] if a referenced variable is not set
+#[cfg(feature = "expand-vars")]
+pub fn expand_full<P>(path: &P) -> Result<Cow<'_, Path>, HomeDirError>
+where
+    P: AsRef<Path> + ?Sized,
+{
+    let home_dir = home_dir()?;
+    expand_full_with(path, home_dir, |name| std::env::var(name).ok())
+}
+
+/// Splits off a leading `~` or `~name` component from `path`.
+///
+/// Returns `Some((name, rest))` where `name` is `None` for a bare `~` and
+/// `Some(name)` for `~name`, and `rest` is everything after that first
+/// component. Returns `None` if `path` does not start with a `~`-prefixed
+/// component.
+fn split_tilde_component(path: &Path) -> Option<(Option<&str>, &Path)> {
+    let mut components = path.components();
+    let std::path::Component::Normal(first) = components.next()? else {
+        return None;
+    };
+    let name = first.to_str()?.strip_prefix(TILDE)?;
+    let rest = components.as_path();
+
+    Some((if name.is_empty() { None } else { Some(name) }, rest))
+}
+
+/// Expands `~` or `~name` in a path, using `home_dir` for a bare `~` and
+/// `lookup` to resolve `~name` to that user's home directory.
+///
+/// If the path does not start with a `~`-prefixed component, it is returned
+/// unchanged. If `lookup` returns `None` for a given name, the path is also
+/// returned unchanged, since the caller could not resolve that user.
+///
+/// # Example
+///
+/// ```rust
+/// use zeroten_expand_tilde::expand_tilde_user_with;
+/// use std::path::{Path, PathBuf};
+///
+/// let path = Path::new("~alice/projects");
+/// assert_eq!(
+///     expand_tilde_user_with(path, "/home/user", |name| {
+///         (name == "alice").then(|| PathBuf::from("/home/alice"))
+///     }),
+///     PathBuf::from("/home/alice/projects")
+/// );
+/// ```
+pub fn expand_tilde_user_with<P, H, F>(path: &P, home_dir: H, lookup: F) -> Cow<'_, Path>
+where
+    P: AsRef<Path> + ?Sized,
+    H: AsRef<Path>,
+    F: FnOnce(&str) -> Option<PathBuf>,
+{
+    fn inner<'a>(
+        path: &'a Path,
+        home_dir: &Path,
+        lookup: impl FnOnce(&str) -> Option<PathBuf>,
+    ) -> Cow<'a, Path> {
+        match split_tilde_component(path) {
+            Some((None, rest)) => home_dir.join(rest).into(),
+            Some((Some(name), rest)) => {
+                lookup(name).map_or_else(|| path.into(), |home| home.join(rest).into())
+            }
+            None => path.into(),
+        }
+    }
+
+    inner(path.as_ref(), home_dir.as_ref(), lookup)
+}
+
+/// Expands `~` or `~name` in a path, using the current user's home directory
+/// for a bare `~` and the OS user database for `~name`.
+///
+/// On Unix, `~name` is resolved via a `getpwnam_r`-style lookup. Per-user
+/// home directory lookup is not generally available on other platforms.
+///
+/// # Errors
+///
+/// - [`HomeDirError::NotFounded`] if the home directory cannot be determined
+/// - [`HomeDirError::Empty`] if the home directory is empty
+/// - [`HomeDirError::UserNotFound`] if `~name` does not match a known user
+pub fn expand_tilde_user<P>(path: &P) -> Result<Cow<'_, Path>, HomeDirError>
+where
+    P: AsRef<Path> + ?Sized,
+{
+    let path = path.as_ref();
+
+    match split_tilde_component(path) {
+        Some((None, rest)) => Ok(home_dir()?.join(rest).into()),
+        Some((Some(name), rest)) => {
+            let home = system_user_home_dir(name)?.ok_or_else(|| HomeDirError::UserNotFound {
+                name: name.to_owned(),
+            })?;
+            Ok(home.join(rest).into())
+        }
+        None => Ok(path.into()),
+    }
+}
+
+/// Looks up a user's home directory in the OS user database.
+///
+/// Returns `Ok(None)` if the user database has no entry for `name`.
+///
+/// # Errors
+///
+/// Returns [`HomeDirError::UserLookupUnsupported`] on platforms where
+/// per-user home directory lookup is not implemented.
+#[allow(clippy::unnecessary_wraps)]
+fn system_user_home_dir(name: &str) -> Result<Option<PathBuf>, HomeDirError> {
+    #[cfg(unix)]
+    {
+        Ok(unix::getpwnam_home_dir(name))
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = name;
+        Err(HomeDirError::UserLookupUnsupported)
+    }
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::ffi::{CStr, CString, OsStr};
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::PathBuf;
+
+    /// Looks up `name` in the system user database via `getpwnam_r`.
+    ///
+    /// Returns `None` if there is no such user, or if the lookup otherwise
+    /// fails.
+    pub(crate) fn getpwnam_home_dir(name: &str) -> Option<PathBuf> {
+        let c_name = CString::new(name).ok()?;
+        let mut pwd: libc::passwd = unsafe { std::mem::zeroed() };
+        let mut result: *mut libc::passwd = std::ptr::null_mut();
+        let mut buf = vec![0_u8; 1024];
+
+        loop {
+            let ret = unsafe {
+                libc::getpwnam_r(
+                    c_name.as_ptr(),
+                    &raw mut pwd,
+                    buf.as_mut_ptr().cast(),
+                    buf.len(),
+                    &raw mut result,
+                )
+            };
+
+            if ret == 0 {
+                break;
+            }
+            if ret == libc::ERANGE {
+                buf.resize(buf.len() * 2, 0);
+                continue;
+            }
+            return None;
+        }
+
+        if result.is_null() {
+            return None;
+        }
+
+        let dir = unsafe { CStr::from_ptr(pwd.pw_dir) };
+        Some(PathBuf::from(OsStr::from_bytes(dir.to_bytes())))
+    }
+}
+
 mod sealed {
     pub trait Sealed {}
 }
@@ -176,12 +566,64 @@ impl ExpandTilde for Path {
 
 impl Sealed for Path {}
 
+/// A trait for folding a home directory prefix back into a tilde.
+pub trait FoldTilde: Sealed {
+    /// Rewrites a leading `home_dir` prefix in the path back to `~`.
+    ///
+    /// If the path starts with `home_dir`, the prefix is replaced with `~`.
+    /// Otherwise, the original path is returned unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use zeroten_expand_tilde::FoldTilde;
+    /// use std::path::{Path, PathBuf};
+    ///
+    /// let home = "/home/user";
+    /// let path = Path::new("/home/user/docs");
+    /// assert_eq!(path.fold_tilde_with(home), PathBuf::from("~/docs"));
+    /// ```
+    fn fold_tilde_with<H: AsRef<Path>>(&self, home_dir: H) -> Cow<'_, Path>;
+
+    /// Rewrites a leading current-user home-directory prefix in the path
+    /// back to `~`.
+    ///
+    /// # Errors
+    ///
+    /// - [`HomeDirError::NotFounded`] if the home directory cannot be determined
+    /// - [`HomeDirError::Empty`] if the home directory is empty
+    fn fold_tilde(&self) -> Result<Cow<'_, Path>, HomeDirError>;
+}
+
+impl FoldTilde for Path {
+    fn fold_tilde_with<H: AsRef<Path>>(&self, home_dir: H) -> Cow<'_, Path> {
+        fold_tilde_with(self, home_dir)
+    }
+
+    fn fold_tilde(&self) -> Result<Cow<'_, Path>, HomeDirError> {
+        fold_tilde(self)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum HomeDirError {
     /// The home directory is empty
     Empty,
     /// The home directoy not founed
     NotFounded,
+    /// No user with the given name was found in the OS user database
+    UserNotFound {
+        /// The username that was looked up
+        name: String,
+    },
+    /// Per-user home directory lookup is not supported on this platform
+    UserLookupUnsupported,
+    /// A referenced `$VAR`/`${VAR}` has no value
+    #[cfg(feature = "expand-vars")]
+    VarNotFound {
+        /// The variable name that was looked up
+        name: String,
+    },
 }
 
 impl fmt::Display for HomeDirError {
@@ -189,6 +631,17 @@ impl fmt::Display for HomeDirError {
         match self {
             HomeDirError::Empty => write!(f, "the home directory is empty"),
             HomeDirError::NotFounded => write!(f, "the home directoy not founed"),
+            HomeDirError::UserNotFound { name } => write!(f, "no such user: {name}"),
+            HomeDirError::UserLookupUnsupported => {
+                write!(
+                    f,
+                    "per-user home directory lookup is not supported on this platform"
+                )
+            }
+            #[cfg(feature = "expand-vars")]
+            HomeDirError::VarNotFound { name } => {
+                write!(f, "environment variable not found: {name}")
+            }
         }
     }
 }
@@ -214,4 +667,110 @@ mod test {
             PathBuf::from("/home/user")
         );
     }
+
+    #[test]
+    fn test_expand_tilde_user_with() {
+        let lookup = |name: &str| (name == "alice").then(|| PathBuf::from("/home/alice"));
+
+        assert_eq!(
+            PathBuf::from("/home/alice/projects"),
+            expand_tilde_user_with("~alice/projects", "/home/user", lookup).into_owned()
+        );
+        assert_eq!(
+            PathBuf::from("/home/user/docs"),
+            expand_tilde_user_with("~/docs", "/home/user", lookup).into_owned()
+        );
+        assert_eq!(
+            PathBuf::from("~bob/docs"),
+            expand_tilde_user_with("~bob/docs", "/home/user", lookup).into_owned()
+        );
+        assert_eq!(
+            PathBuf::from("some/dir"),
+            expand_tilde_user_with("some/dir", "/home/user", lookup).into_owned()
+        );
+    }
+
+    #[test]
+    fn test_expand_tilde_with_standalone_component_only() {
+        assert_eq!(
+            PathBuf::from("~foo/bar"),
+            expand_tilde_with("~foo/bar", "/home/user").into_owned()
+        );
+        assert_eq!(
+            PathBuf::from("foo/~"),
+            expand_tilde_with("foo/~", "/home/user").into_owned()
+        );
+        assert_eq!(
+            expand_tilde_with("~", "/home/user"),
+            PathBuf::from("/home/user")
+        );
+    }
+
+    #[test]
+    fn test_expand_tilde_with_root_home() {
+        assert_eq!(expand_tilde_with("~", "/"), PathBuf::from("/"));
+        assert_eq!(expand_tilde_with("~/x", "/"), PathBuf::from("/x"));
+        assert_eq!(expand_tilde_with("~//x", "/"), PathBuf::from("/x"));
+    }
+
+    #[test]
+    fn test_fold_tilde_with() {
+        assert_eq!(
+            PathBuf::from("~/docs"),
+            fold_tilde_with("/home/user/docs", "/home/user").into_owned()
+        );
+        assert_eq!(
+            PathBuf::from("/other/docs"),
+            fold_tilde_with("/other/docs", "/home/user").into_owned()
+        );
+        assert_eq!(
+            fold_tilde_with("/home/user", "/home/user"),
+            PathBuf::from("~")
+        );
+    }
+
+    #[test]
+    fn test_fold_tilde_expand_tilde_round_trip() {
+        let home = "/home/user";
+        let path = Path::new("~/some/dir");
+        let expanded = expand_tilde_with(path, home);
+        let folded = fold_tilde_with(&expanded, home);
+
+        assert_eq!(folded, path);
+    }
+
+    #[cfg(feature = "expand-vars")]
+    #[test]
+    fn test_expand_vars_with() {
+        let lookup = |name: &str| (name == "HOME").then(|| "/home/user".to_owned());
+
+        assert_eq!(
+            PathBuf::from("/home/user/docs"),
+            expand_vars_with("$HOME/docs", lookup).unwrap().into_owned()
+        );
+        assert_eq!(
+            PathBuf::from("/home/user/docs"),
+            expand_vars_with("${HOME}/docs", lookup)
+                .unwrap()
+                .into_owned()
+        );
+        assert_eq!(
+            PathBuf::from("some/dir"),
+            expand_vars_with("some/dir", lookup).unwrap().into_owned()
+        );
+        assert!(expand_vars_with("$MISSING", lookup).is_err());
+    }
+
+    #[cfg(feature = "expand-vars")]
+    #[test]
+    fn test_expand_full_with() {
+        let lookup = |name: &str| (name == "PROJECT").then(|| "crate".to_owned());
+
+        assert_eq!(
+            PathBuf::from("/home/user/crate"),
+            expand_full_with("~/$PROJECT", "/home/user", lookup)
+                .unwrap()
+                .into_owned()
+        );
+    }
 }